@@ -2,10 +2,15 @@
 // Process struct as the process control block
 // Author: Hank Bao
 
+use std::fmt;
+
 pub struct Process {
     pid: u32,
+    priority: Priority,
     io_interval: u32,
     io_length: u32,
+    io_model: IoModel,
+    io_wait_samples: Vec<u32>,
     workload: u32,
     work_done: u32,
     start_time: u32,
@@ -13,6 +18,9 @@ pub struct Process {
     turnaround_time: u32,
     response_time: u32,
     allotment: u32,
+    non_preemptible: bool,
+    deadline: Option<u32>,
+    boost_floor: usize,
     state: ProcessState,
 }
 
@@ -20,6 +28,7 @@ pub struct Process {
 impl Process {
     pub fn new(
         pid: u32,
+        priority: Priority,
         io_interval: u32,
         io_length: u32,
         workload: u32,
@@ -27,8 +36,11 @@ impl Process {
     ) -> Process {
         Process {
             pid,
+            priority,
             io_interval,
             io_length,
+            io_model: IoModel::Fixed,
+            io_wait_samples: Vec::new(),
             workload,
             work_done: 0,
             start_time: arrival_time,
@@ -36,6 +48,9 @@ impl Process {
             turnaround_time: 0,
             response_time: 0,
             allotment: 0,
+            non_preemptible: false,
+            deadline: None,
+            boost_floor: priority.default_boost_floor(),
             state: ProcessState::Ready,
         }
     }
@@ -44,6 +59,14 @@ impl Process {
         self.pid
     }
 
+    pub fn priority(&self) -> Priority {
+        self.priority
+    }
+
+    pub fn set_priority(&mut self, priority: Priority) {
+        self.priority = priority;
+    }
+
     pub fn io_interval(&self) -> u32 {
         self.io_interval
     }
@@ -52,6 +75,26 @@ impl Process {
         self.io_length
     }
 
+    /// Configure how this process's actual I/O block durations are sampled.
+    /// Defaults to `IoModel::Fixed`, which always blocks for exactly
+    /// `io_length` ticks, matching the simulator's original behaviour.
+    /// `io_length` keeps acting as the mean fed into `IoModel::Exponential`;
+    /// `IoModel::UniformRange` ignores it and samples from its own bounds.
+    pub fn set_io_model(&mut self, io_model: IoModel) {
+        self.io_model = io_model;
+    }
+
+    /// Actual durations sampled for each I/O block this process has taken
+    /// so far, in order.
+    pub fn io_wait_samples(&self) -> &[u32] {
+        &self.io_wait_samples
+    }
+
+    /// Total ticks this process has spent waiting on I/O so far.
+    pub fn total_io_wait(&self) -> u32 {
+        self.io_wait_samples.iter().sum()
+    }
+
     pub fn workload(&self) -> u32 {
         self.workload
     }
@@ -72,6 +115,36 @@ impl Process {
         self.turnaround_time
     }
 
+    /// Deadline relative to `start_time`, if this process belongs to a
+    /// real-time workload. Purely informational: it has no scheduling
+    /// effect on its own and is only consumed by `missed_deadline()` and
+    /// `lateness()` (and, in the future, by a deadline-aware policy).
+    pub fn deadline(&self) -> Option<u32> {
+        self.deadline
+    }
+
+    pub fn set_deadline(&mut self, deadline: Option<u32>) {
+        self.deadline = deadline;
+    }
+
+    /// Whether the process finished after its deadline. Always `false` for
+    /// a process with no deadline, or one that has not finished yet.
+    pub fn missed_deadline(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => self.is_finished() && self.turnaround_time > deadline,
+            None => false,
+        }
+    }
+
+    /// How far past the deadline the process finished, in ticks. Negative
+    /// if it finished early, `0` if it has no deadline or has not finished.
+    pub fn lateness(&self) -> i64 {
+        match self.deadline {
+            Some(deadline) if self.is_finished() => self.turnaround_time as i64 - deadline as i64,
+            _ => 0,
+        }
+    }
+
     pub fn response_time(&self) -> u32 {
         self.response_time
     }
@@ -84,6 +157,16 @@ impl Process {
         self.allotment
     }
 
+    /// Whether this process runs its full burst without being cut off at a
+    /// quantum boundary, modelling cooperative or CPU-bound kernel work.
+    pub fn is_non_preemptible(&self) -> bool {
+        self.non_preemptible
+    }
+
+    pub fn set_non_preemptible(&mut self, non_preemptible: bool) {
+        self.non_preemptible = non_preemptible;
+    }
+
     pub fn is_blocked(&self) -> bool {
         match self.state {
             ProcessState::Blocked => true,
@@ -98,32 +181,74 @@ impl Process {
         }
     }
 
-    pub fn run(&mut self, quantum: u32, at: u32, queue: usize) -> u32 {
+    /// Advance the process by one scheduling decision, returning the actual
+    /// run time together with the trace events produced along the way.
+    ///
+    /// Callers that want the old on-the-fly printing can simply do
+    /// `for event in events { println!("{}", event); }`; the scheduler is
+    /// now free to buffer, filter, or render the events however it likes.
+    pub fn run(&mut self, quantum: u32, at: u32, queue: usize) -> (u32, Vec<TraceEvent>) {
         // record the response time
         if self.response_time == 0 {
             assert!(at >= self.start_time);
             self.response_time = at - self.start_time;
         }
 
-        match self.state {
-            ProcessState::Ready => self.run_from_ready(quantum, at, queue),
-            ProcessState::Running => self.run_from_running(quantum, at, queue),
-            ProcessState::Blocked => self.run_from_blocked(quantum, at, queue),
+        let mut events = Vec::new();
+        let run_time = match self.state {
+            ProcessState::Ready => self.run_from_ready(quantum, at, queue, &mut events),
+            ProcessState::Running => self.run_from_running(quantum, at, queue, &mut events),
+            ProcessState::Blocked => self.run_from_blocked(quantum, at, queue, &mut events),
             ProcessState::Finished => panic!("Run a finished process {}.", self.pid),
-        }
+        };
+
+        (run_time, events)
     }
 
-    fn run_from_ready(&mut self, quantum: u32, at: u32, queue: usize) -> u32 {
+    fn run_from_ready(
+        &mut self,
+        quantum: u32,
+        at: u32,
+        queue: usize,
+        events: &mut Vec<TraceEvent>,
+    ) -> u32 {
         self.state = ProcessState::Running;
-        println!("[{}:<{}>] Process {} start running.", at, queue, self.pid);
+        events.push(TraceEvent::Started {
+            pid: self.pid,
+            at,
+            queue,
+        });
+
+        self.run_from_running(quantum, at, queue, events)
+    }
 
-        self.run_from_running(quantum, at, queue)
+    /// The MLFQ level a periodic priority boost may drop this process to.
+    /// A `High` process must never be boosted below its class floor, even
+    /// if lower-priority classes are allowed to land further down. Defaults
+    /// to the priority class's `default_boost_floor()` but can be tuned per
+    /// process independently of the class used to pick the starting queue.
+    pub fn boost_floor(&self) -> usize {
+        self.boost_floor
     }
 
-    fn run_from_running(&mut self, quantum: u32, at: u32, queue: usize) -> u32 {
+    pub fn set_boost_floor(&mut self, boost_floor: usize) {
+        self.boost_floor = boost_floor;
+    }
+
+    fn run_from_running(
+        &mut self,
+        quantum: u32,
+        at: u32,
+        queue: usize,
+        events: &mut Vec<TraceEvent>,
+    ) -> u32 {
         assert_eq!(self.state, ProcessState::Running);
         assert!(self.allotment > 0);
 
+        // A non-preemptible process ignores the quantum boundary entirely
+        // and runs until it either hits its next I/O point or completes.
+        let quantum = if self.non_preemptible { u32::MAX } else { quantum };
+
         let run_time: u32; // actual run time
         let work_left = self.workload - self.work_done; // work left
 
@@ -133,7 +258,9 @@ impl Process {
             if work_before_io < work_left && work_before_io <= quantum {
                 run_time = work_before_io;
                 self.work_done += run_time;
-                self.next_schedule_time = at + self.io_length;
+                let io_wait = self.io_model.sample(self.io_length);
+                self.io_wait_samples.push(io_wait);
+                self.next_schedule_time = at + io_wait;
                 self.state = ProcessState::Blocked;
             } else if work_left <= quantum {
                 run_time = work_left;
@@ -164,53 +291,252 @@ impl Process {
 
         assert!(run_time > 0);
 
-        // Update allotment
-        if run_time < self.allotment {
-            self.allotment -= run_time;
-        } else {
+        // Update allotment. A non-preemptible process charges its whole
+        // allotment in a single shot, even if it blocked on I/O early,
+        // since it was never available to be cut off mid-burst.
+        if self.non_preemptible || run_time >= self.allotment {
             self.allotment = 0;
+        } else {
+            self.allotment -= run_time;
         }
 
-        // Print status
-        match self.state {
-            ProcessState::Running => {
-                println!(
-                    "[{}:<{}>] Process {} has run for {}.",
-                    at + run_time,
-                    queue,
-                    self.pid,
-                    run_time
-                )
-            }
-            ProcessState::Blocked => println!(
-                "[{}:<{}>] Process {} has run for {}, then blocked. It will perform I/O for {}",
-                at + run_time,
-                queue,
-                self.pid,
-                run_time,
-                self.io_length
-            ),
-            ProcessState::Finished => println!(
-                "[{}:<{}>] Process {} has run for {}, then finished.",
-                at + run_time,
-                queue,
-                self.pid,
-                run_time
-            ),
+        // Record a single trace event for this run segment, carrying
+        // whatever it led to, so the rendered line matches the simulator's
+        // original single-line-per-transition output.
+        let outcome = match self.state {
+            ProcessState::Running => RunOutcome::Continued,
+            ProcessState::Blocked => RunOutcome::Blocked {
+                io_length: *self.io_wait_samples.last().unwrap(),
+            },
+            ProcessState::Finished => RunOutcome::Finished {
+                turnaround: self.turnaround_time,
+            },
             _ => panic!("Process {} is in an invalid state.", self.pid),
-        }
+        };
+        events.push(TraceEvent::Ran {
+            pid: self.pid,
+            at: at + run_time,
+            run_time,
+            queue,
+            outcome,
+        });
 
         run_time
     }
 
-    fn run_from_blocked(&mut self, quantum: u32, at: u32, queue: usize) -> u32 {
+    fn run_from_blocked(
+        &mut self,
+        quantum: u32,
+        at: u32,
+        queue: usize,
+        events: &mut Vec<TraceEvent>,
+    ) -> u32 {
         self.state = ProcessState::Running;
-        println!(
-            "[{}:<{}>] Process {} resume running from I/O.",
-            at, queue, self.pid
-        );
+        events.push(TraceEvent::Resumed {
+            pid: self.pid,
+            at,
+            queue,
+        });
+
+        self.run_from_running(quantum, at, queue, events)
+    }
+}
+
+/// Minimal seeded pseudo-random generator (SplitMix64) backing `IoModel`.
+/// Self-contained on purpose: this crate has no manifest to pull in a
+/// `rand`-style dependency, so the model rolls its own tiny, reproducible
+/// generator instead.
+#[derive(Clone)]
+pub struct Rng64(u64);
+
+impl Rng64 {
+    fn new(seed: u64) -> Rng64 {
+        Rng64(seed)
+    }
 
-        self.run_from_running(quantum, at, queue)
+    fn next_u64(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E3779B97F4A7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58476D1CE4E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D049BB133111EB);
+        z ^ (z >> 31)
+    }
+
+    /// A uniform value in `[min, max]`.
+    fn gen_range_inclusive(&mut self, min: u32, max: u32) -> u32 {
+        let span = (max - min) as u64 + 1;
+        min + (self.next_u64() % span) as u32
+    }
+
+    /// A uniform value in the open interval `(0, 1)`, clear of both
+    /// endpoints so the exponential sampler's `ln()` never sees 0.0 or 1.0.
+    fn gen_open01(&mut self) -> f64 {
+        let x = (self.next_u64() >> 11) as f64 / (1u64 << 53) as f64;
+        x.max(f64::EPSILON).min(1.0 - f64::EPSILON)
+    }
+}
+
+/// How long a process actually blocks each time it performs I/O. Defaults
+/// to `Fixed`, which reproduces the simulator's original deterministic
+/// behaviour; the other variants sample a noisy duration from a seeded RNG,
+/// so repeated runs stay reproducible. `Exponential` uses the process's
+/// `io_length` as its mean; `UniformRange` uses only its own bounds.
+pub enum IoModel {
+    /// Always block for exactly `io_length` ticks.
+    Fixed,
+    /// Block for a duration drawn uniformly from `[min, max]`.
+    UniformRange { min: u32, max: u32, rng: Rng64 },
+    /// Block for a duration drawn from an exponential distribution whose
+    /// mean is `io_length`.
+    Exponential { rng: Rng64 },
+}
+
+impl IoModel {
+    pub fn fixed() -> IoModel {
+        IoModel::Fixed
+    }
+
+    pub fn uniform_range(min: u32, max: u32, seed: u64) -> IoModel {
+        IoModel::UniformRange {
+            min,
+            max,
+            rng: Rng64::new(seed),
+        }
+    }
+
+    pub fn exponential(seed: u64) -> IoModel {
+        IoModel::Exponential {
+            rng: Rng64::new(seed),
+        }
+    }
+
+    /// Sample an actual I/O block duration, given the process's configured
+    /// mean/parameter (`io_length`).
+    fn sample(&mut self, io_length: u32) -> u32 {
+        match self {
+            IoModel::Fixed => io_length,
+            IoModel::UniformRange { min, max, rng } => rng.gen_range_inclusive(*min, *max),
+            IoModel::Exponential { rng } => {
+                let mean = io_length.max(1) as f64;
+                let u = rng.gen_open01();
+                (-mean * (1.0 - u).ln()).round().max(1.0) as u32
+            }
+        }
+    }
+}
+
+/// Static priority class a process is admitted with, set at `new()`.
+/// Unlike the MLFQ level (which moves a process up and down based on its
+/// observed CPU burst history), it changes only through an explicit
+/// `Process::set_priority()` call; it picks the starting queue and seeds
+/// the boost floor. `set_priority()` does not update an existing process's
+/// boost floor.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum Priority {
+    High,
+    Normal,
+    Low,
+}
+
+impl Priority {
+    /// The MLFQ level a freshly-arrived process of this class is admitted
+    /// to, so interactive (`High`) work starts ahead of batch work instead
+    /// of competing from the bottom queue.
+    pub fn starting_queue(self) -> usize {
+        match self {
+            Priority::High => 0,
+            Priority::Normal => 1,
+            Priority::Low => 2,
+        }
+    }
+
+    /// The boost floor a process of this class is seeded with at `new()`.
+    /// This is only a starting point: `Process::set_boost_floor()` can
+    /// override it per process, independently of `starting_queue()`, to
+    /// model e.g. a `Normal` job whose floor is tightened to queue 0 at runtime.
+    pub fn default_boost_floor(self) -> usize {
+        match self {
+            Priority::High => 0,
+            Priority::Normal => 1,
+            Priority::Low => 2,
+        }
+    }
+}
+
+/// What a run segment led to: still holding the CPU, blocking on I/O, or
+/// completing. Carried by `TraceEvent::Ran` so the whole transition renders
+/// as the single line the simulator originally printed for it.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum RunOutcome {
+    Continued,
+    Blocked { io_length: u32 },
+    Finished { turnaround: u32 },
+}
+
+/// A structured record of a state transition made by a `Process` during
+/// `run()`. The scheduler owns what to do with these (print them, collect
+/// them for a test assertion, feed them to a UI); `Process` itself no longer
+/// prints anything.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum TraceEvent {
+    Started { pid: u32, at: u32, queue: usize },
+    Resumed { pid: u32, at: u32, queue: usize },
+    Ran {
+        pid: u32,
+        at: u32,
+        run_time: u32,
+        queue: usize,
+        outcome: RunOutcome,
+    },
+}
+
+// Thin presentation layer reproducing the textual output the simulator used
+// to print directly from the PCB.
+impl fmt::Display for TraceEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            TraceEvent::Started { pid, at, queue } => {
+                write!(f, "[{}:<{}>] Process {} start running.", at, queue, pid)
+            }
+            TraceEvent::Resumed { pid, at, queue } => write!(
+                f,
+                "[{}:<{}>] Process {} resume running from I/O.",
+                at, queue, pid
+            ),
+            TraceEvent::Ran {
+                pid,
+                at,
+                run_time,
+                queue,
+                outcome: RunOutcome::Continued,
+            } => write!(
+                f,
+                "[{}:<{}>] Process {} has run for {}.",
+                at, queue, pid, run_time
+            ),
+            TraceEvent::Ran {
+                pid,
+                at,
+                run_time,
+                queue,
+                outcome: RunOutcome::Blocked { io_length },
+            } => write!(
+                f,
+                "[{}:<{}>] Process {} has run for {}, then blocked. It will perform I/O for {}",
+                at, queue, pid, run_time, io_length
+            ),
+            TraceEvent::Ran {
+                pid,
+                at,
+                run_time,
+                queue,
+                outcome: RunOutcome::Finished { .. },
+            } => write!(
+                f,
+                "[{}:<{}>] Process {} has run for {}, then finished.",
+                at, queue, pid, run_time
+            ),
+        }
     }
 }
 